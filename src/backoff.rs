@@ -0,0 +1,40 @@
+//! A small spin/yield backoff used by [`PingPongCell::transact`](crate::PingPongCell::transact)
+//! while waiting to acquire the cell, so contention burns progressively
+//! fewer cycles instead of hammering the same compare-exchange in a tight
+//! loop. Mirrors the shape of crossbeam's `Backoff`, inlined so the crate
+//! can stay `no_std` without an extra dependency.
+
+const SPIN_LIMIT: u32 = 6;
+const YIELD_LIMIT: u32 = 10;
+
+pub(crate) struct Backoff {
+    step: u32,
+}
+
+impl Backoff {
+    pub(crate) fn new() -> Self {
+        Backoff { step: 0 }
+    }
+
+    /// Backs off a little more than last time, spinning while the wait is
+    /// likely to be short and escalating to a thread yield (when the
+    /// `std` feature is enabled) once it looks like we're contending with
+    /// something that's actually doing work.
+    pub(crate) fn snooze(&mut self) {
+        if self.step <= SPIN_LIMIT {
+            for _ in 0..1u32 << self.step {
+                core::hint::spin_loop();
+            }
+        } else {
+            #[cfg(feature = "std")]
+            std::thread::yield_now();
+            #[cfg(not(feature = "std"))]
+            for _ in 0..1u32 << SPIN_LIMIT {
+                core::hint::spin_loop();
+            }
+        }
+        if self.step <= YIELD_LIMIT {
+            self.step += 1;
+        }
+    }
+}
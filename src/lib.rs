@@ -1,7 +1,17 @@
 #![no_std]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 use core::cell::UnsafeCell;
-use core::sync::atomic::Ordering::{Acquire, Release};
-use core::sync::atomic::{spin_loop_hint, AtomicBool};
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+
+use backoff::Backoff;
+
+mod backoff;
+pub mod oneshot;
+pub mod watch;
 
 /// An Atomic Cell game for up to two players.
 #[derive(Debug)]
@@ -51,8 +61,13 @@ impl<T> PingPongCell<T> {
     where
         F: FnOnce(&mut Option<T>) -> R,
     {
-        while self.is_working.compare_and_swap(false, true, Acquire) {
-            spin_loop_hint();
+        let mut backoff = Backoff::new();
+        while self
+            .is_working
+            .compare_exchange_weak(false, true, Acquire, Relaxed)
+            .is_err()
+        {
+            backoff.snooze();
         }
         let ret = unsafe { fun(&mut *self.value.get()) };
         self.is_working.store(false, Release);
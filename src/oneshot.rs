@@ -0,0 +1,282 @@
+//! A single-producer, single-consumer, one-shot channel built on top of
+//! [`PingPongCell`](crate::PingPongCell).
+//!
+//! Every call to [`oneshot`] heap-allocates a fresh `Arc<Inner<T>>` to hold
+//! the state shared between the [`Sender`] and the [`Receiver`]. Code that
+//! creates and tears down large numbers of short-lived channels should
+//! look at the [`pool`] module instead, which recycles slots from a slab
+//! rather than allocating one.
+
+use crate::PingPongCell;
+use alloc::sync::Arc;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use futures_micro::{sleep, waker};
+
+pub mod pool;
+
+/// Creates a new one-shot channel, returning the paired [`Sender`] and
+/// [`Receiver`].
+pub fn oneshot<T: Send>() -> (Sender<T>, Receiver<T>) {
+    let inner = Arc::new(Inner { cell: PingPongCell::new(None), cancel: PingPongCell::new(None) });
+    let sender = Sender { inner: Some(inner.clone()) };
+    let receiver = Receiver { inner: Some(inner) };
+    (sender, receiver)
+}
+
+/// The channel was closed before a value could be sent/received.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Closed();
+
+#[derive(Debug)]
+pub enum WaitError {
+    AlreadyAwaited(Waker),
+}
+
+pub struct Sender<T: Send> {
+    inner: Option<Arc<Inner<T>>>,
+}
+
+// `wait` returns a future that resolves once a receiver is listening.
+impl<T: Send> Sender<T> {
+
+    /// Closes the channel by causing an immediate drop
+    pub fn close(self) { }
+
+    /// Returns true if the Receiver has already been dropped, meaning
+    /// whatever value is eventually sent will just be discarded.
+    pub fn is_canceled(&self) -> bool {
+        if let Some(ref inner) = &self.inner {
+            inner.cell.transact(|state| matches!(state, Some(State::Closed)))
+        } else {
+            true
+        }
+    }
+
+    /// Polls for the Receiver having been dropped, so that expensive work
+    /// computing the value to send can be abandoned early. Keeps its own
+    /// waker slot, separate from the one `Receiver`/`Sender::wait` use to
+    /// signal a value or readiness, so it never steals their wakeup.
+    pub fn poll_canceled(&mut self, context: &mut Context) -> Poll<()> {
+        if let Some(ref inner) = &self.inner {
+            // Register first, then check: if the Receiver races us and
+            // drops right after we store the waker, its `Drop` impl will
+            // see and fire it, so we never miss a wakeup.
+            inner.cancel.put(context.waker().clone());
+            if inner.cell.transact(|state| matches!(state, Some(State::Closed))) {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        } else {
+            Poll::Ready(())
+        }
+    }
+
+    /// Waits for a Receiver to be waiting for us to send something
+    /// (i.e. allows you to produce a value to send on demand).
+    pub async fn wait(self) -> Result<Self, Closed> {
+        while let Some(ref inner) = &self.inner {
+            let wake_me = waker().await;
+            let ret = inner.cell.transact(|state| {
+                match state.take() {
+                    Some(State::Closed) => Poll::Ready(Err(Closed())),
+                    Some(State::Waker(waker)) => {
+                        *state = Some(State::Waker(waker));
+                        Poll::Ready(Ok(()))
+                    }
+                    _ => {
+                        *state = Some(State::Waker(wake_me));
+                        Poll::Pending
+                    }
+                }
+            });
+            match ret {
+                Poll::Pending => sleep().await,
+                Poll::Ready(Ok(())) => { return Ok(self); }
+                Poll::Ready(Err(Closed())) => {return Err(Closed()); }
+            }
+        }
+        Err(Closed())
+    }
+
+    /// Sends a message on the channel
+    pub fn send(self, value: T) -> Result<(), Closed> {
+        if let Some(ref inner) = &self.inner {
+            let ret = inner.cell.transact(|state| {
+                match state.take() {
+                    Some(State::Closed) => Err(Closed()),
+                    Some(State::Waker(waker)) => {
+                        *state = Some(State::Ready(value));
+                        Ok(Some(waker))
+                    }
+                    _ => {
+                        *state = Some(State::Ready(value));
+                        Ok(None)
+                    }
+                }
+            });
+            match ret {
+                Ok(Some(waker)) => {
+                    waker.wake();
+                    Ok(())
+                }
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            }
+        } else {
+            Err(Closed()) // not sure how you got here tbh
+        }
+    }
+}
+
+pub struct Receiver<T: Send> {
+    inner: Option<Arc<Inner<T>>>,
+}
+
+impl<T: Send> Receiver<T> {
+    /// Closes the channel by causing an immediate drop
+    pub fn close(self) { }
+
+    /// Checks for a value without blocking or registering a waker.
+    ///
+    /// Returns `Ok(Some(value))` if the Sender has already sent one,
+    /// `Ok(None)` if it hasn't yet, and `Err(Closed)` if the Sender was
+    /// dropped without sending. Unlike polling this as a `Future`, a
+    /// `None` result does not register a waker (and leaves any waker
+    /// already registered by a previous poll untouched), so callers that
+    /// need a wakeup still need to `await` the `Receiver`.
+    pub fn try_recv(&mut self) -> Result<Option<T>, Closed> {
+        if let Some(ref inner) = &self.inner {
+            inner.cell.transact(|state| match state.take() {
+                Some(State::Ready(value)) => Ok(Some(value)),
+                Some(State::Closed) => {
+                    *state = Some(State::Closed);
+                    Err(Closed())
+                }
+                other => {
+                    *state = other;
+                    Ok(None)
+                }
+            })
+        } else {
+            Err(Closed())
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Send> Receiver<T> {
+    /// Drives this channel to completion on the current thread, for use
+    /// from code that isn't running inside an async executor.
+    pub fn blocking_recv(mut self) -> Result<T, Closed> {
+        let waker = std::task::Waker::from(std::sync::Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match Pin::new(&mut self).poll(&mut cx) {
+                Poll::Ready(result) => return result,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+struct ThreadWaker(std::thread::Thread);
+
+#[cfg(feature = "std")]
+impl std::task::Wake for ThreadWaker {
+    fn wake(self: std::sync::Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+pub(crate) enum State<T> {
+    Waker(Waker),
+    Ready(T),
+    Closed,
+}
+
+struct Inner<T> {
+    cell: PingPongCell<State<T>>,
+    // Holds the waker registered by `Sender::poll_canceled`, kept separate
+    // from `cell` so checking for cancellation never clobbers a waker the
+    // Receiver (or `Sender::wait`) has stored there to await a value.
+    cancel: PingPongCell<Waker>,
+}
+
+impl<T: Send> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if let Some(inner) = &self.inner {
+            let ret = inner.cell.transact(|state| {
+                match state.take() {
+                    Some(State::Waker(waker)) => {
+                        *state = Some(State::Closed);
+                        Some(waker)
+                    }
+                    // Could be Ready or Closed, either is fine
+                    Some(other) => {
+                        *state = Some(other);
+                        None
+                    }
+                    None => {
+                        *state = Some(State::Closed);
+                        None
+                    }
+                }
+            });
+            if let Some(waker) = ret {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T: Send> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        if let Some(inner) = &self.inner {
+            let ret = inner.cell.transact(|state| {
+                if let Some(State::Waker(waker)) = state.take() {
+                    *state = Some(State::Closed);
+                    Some(waker)
+                } else {
+                    *state = Some(State::Closed);
+                    None
+                }
+            });
+            if let Some(waker) = ret {
+                waker.wake();
+            }
+            if let Some(waker) = inner.cancel.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T: Send> Future for Receiver<T> {
+    type Output = Result<T, Closed>;
+    fn poll(self: Pin<&mut Self>, context: &mut Context) -> Poll<Result<T, Closed>> {
+        let this = Pin::into_inner(self);
+        if let Some(inner) = &this.inner {
+            inner.cell.transact(|state| {
+                match state.take() {
+                    Some(State::Closed) => Poll::Ready(Err(Closed())),
+                    Some(State::Ready(value)) => Poll::Ready(Ok(value)),
+                    Some(State::Waker(waker)) => {
+                        *state = Some(State::Waker(context.waker().clone()));
+                        waker.wake();
+                        Poll::Pending
+                    }
+                    None => {
+                        *state = Some(State::Waker(context.waker().clone()));
+                        Poll::Pending
+                    }
+                }
+            })
+        } else {
+            Poll::Ready(Err(Closed()))
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! A pool of reusable one-shot channels.
+//!
+//! [`oneshot`](super::oneshot) heap-allocates a fresh `Arc<Inner<T>>` on
+//! every call, which is wasteful for code that creates and destroys
+//! millions of short-lived request/response channels. [`Pool`] instead
+//! keeps a slab of slots behind a single [`PingPongCell`]: claiming a
+//! channel reuses a free slot (or grows the slab), and dropping both
+//! halves returns the slot to the free list.
+
+use super::{Closed, State};
+use crate::PingPongCell;
+use alloc::sync::Arc;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures_micro::{sleep, waker};
+use slab::Slab;
+
+const SENDER: u8 = 0b01;
+const RECEIVER: u8 = 0b10;
+
+/// Tracks which of the `Sender`/`Receiver` half of a slot are still alive.
+#[derive(Clone, Copy)]
+struct Flags(u8);
+
+impl Flags {
+    fn both() -> Self {
+        Flags(SENDER | RECEIVER)
+    }
+
+    fn clear(&mut self, bit: u8) {
+        self.0 &= !bit;
+    }
+
+    fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+struct Entry<T> {
+    flags: Flags,
+    state: Option<State<T>>,
+}
+
+struct PoolInner<T> {
+    cell: PingPongCell<Slab<Entry<T>>>,
+}
+
+/// A pool of reusable one-shot channel slots.
+///
+/// Cloning a `Pool` is cheap; clones share the same underlying slab.
+pub struct Pool<T> {
+    inner: Arc<PoolInner<T>>,
+}
+
+impl<T> Clone for Pool<T> {
+    fn clone(&self) -> Self {
+        Pool { inner: self.inner.clone() }
+    }
+}
+
+impl<T> Pool<T> {
+    /// Creates a new, empty pool.
+    pub fn new() -> Self {
+        Pool {
+            inner: Arc::new(PoolInner { cell: PingPongCell::new(Some(Slab::new())) }),
+        }
+    }
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Send> Pool<T> {
+    /// Claims a slot from the pool (growing it if none are free) and
+    /// returns a fresh `(Sender, Receiver)` pair backed by it.
+    pub fn channel(&self) -> (Sender<T>, Receiver<T>) {
+        let index = self.inner.cell.transact(|slab| {
+            let slab = slab.as_mut().expect("pool slab is always present");
+            slab.insert(Entry { flags: Flags::both(), state: None })
+        });
+        let sender = Sender { pool: self.clone(), index };
+        let receiver = Receiver { pool: self.clone(), index };
+        (sender, receiver)
+    }
+}
+
+pub struct Sender<T: Send> {
+    pool: Pool<T>,
+    index: usize,
+}
+
+impl<T: Send> Sender<T> {
+    /// Closes the channel by causing an immediate drop
+    pub fn close(self) { }
+
+    /// Waits for a Receiver to be waiting for us to send something
+    /// (i.e. allows you to produce a value to send on demand).
+    pub async fn wait(self) -> Result<Self, Closed> {
+        loop {
+            let wake_me = waker().await;
+            let ret = self.pool.inner.cell.transact(|slab| {
+                let slab = slab.as_mut().expect("pool slab is always present");
+                let entry = &mut slab[self.index];
+                match entry.state.take() {
+                    Some(State::Closed) => Poll::Ready(Err(Closed())),
+                    Some(State::Waker(waker)) => {
+                        entry.state = Some(State::Waker(waker));
+                        Poll::Ready(Ok(()))
+                    }
+                    _ => {
+                        entry.state = Some(State::Waker(wake_me));
+                        Poll::Pending
+                    }
+                }
+            });
+            match ret {
+                Poll::Pending => sleep().await,
+                Poll::Ready(Ok(())) => return Ok(self),
+                Poll::Ready(Err(Closed())) => return Err(Closed()),
+            }
+        }
+    }
+
+    /// Sends a message on the channel
+    pub fn send(self, value: T) -> Result<(), Closed> {
+        let ret = self.pool.inner.cell.transact(|slab| {
+            let slab = slab.as_mut().expect("pool slab is always present");
+            let entry = &mut slab[self.index];
+            match entry.state.take() {
+                Some(State::Closed) => Err(Closed()),
+                Some(State::Waker(waker)) => {
+                    entry.state = Some(State::Ready(value));
+                    Ok(Some(waker))
+                }
+                _ => {
+                    entry.state = Some(State::Ready(value));
+                    Ok(None)
+                }
+            }
+        });
+        match ret {
+            Ok(Some(waker)) => {
+                waker.wake();
+                Ok(())
+            }
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+pub struct Receiver<T: Send> {
+    pool: Pool<T>,
+    index: usize,
+}
+
+impl<T: Send> Receiver<T> {
+    /// Closes the channel by causing an immediate drop
+    pub fn close(self) { }
+}
+
+impl<T: Send> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let waker = self.pool.inner.cell.transact(|slab| {
+            let slab = slab.as_mut().expect("pool slab is always present");
+            let waker = {
+                let entry = &mut slab[self.index];
+                entry.flags.clear(SENDER);
+                match entry.state.take() {
+                    Some(State::Waker(waker)) => {
+                        entry.state = Some(State::Closed);
+                        Some(waker)
+                    }
+                    Some(other) => {
+                        entry.state = Some(other);
+                        None
+                    }
+                    None => {
+                        entry.state = Some(State::Closed);
+                        None
+                    }
+                }
+            };
+            if slab[self.index].flags.is_empty() {
+                slab.remove(self.index);
+            }
+            waker
+        });
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T: Send> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let waker = self.pool.inner.cell.transact(|slab| {
+            let slab = slab.as_mut().expect("pool slab is always present");
+            let waker = {
+                let entry = &mut slab[self.index];
+                entry.flags.clear(RECEIVER);
+                if let Some(State::Waker(waker)) = entry.state.take() {
+                    entry.state = Some(State::Closed);
+                    Some(waker)
+                } else {
+                    entry.state = Some(State::Closed);
+                    None
+                }
+            };
+            if slab[self.index].flags.is_empty() {
+                slab.remove(self.index);
+            }
+            waker
+        });
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T: Send> Future for Receiver<T> {
+    type Output = Result<T, Closed>;
+    fn poll(self: Pin<&mut Self>, context: &mut Context) -> Poll<Result<T, Closed>> {
+        let this = Pin::into_inner(self);
+        this.pool.inner.cell.transact(|slab| {
+            let slab = slab.as_mut().expect("pool slab is always present");
+            let entry = &mut slab[this.index];
+            match entry.state.take() {
+                Some(State::Closed) => Poll::Ready(Err(Closed())),
+                Some(State::Ready(value)) => Poll::Ready(Ok(value)),
+                Some(State::Waker(waker)) => {
+                    entry.state = Some(State::Waker(context.waker().clone()));
+                    waker.wake();
+                    Poll::Pending
+                }
+                None => {
+                    entry.state = Some(State::Waker(context.waker().clone()));
+                    Poll::Pending
+                }
+            }
+        })
+    }
+}
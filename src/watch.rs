@@ -0,0 +1,185 @@
+//! A single-producer, multi-consumer channel that distributes the most
+//! recently published value to any number of [`Receiver`]s.
+//!
+//! Unlike [`oneshot`](crate::oneshot), which hands off a single value
+//! once, a `watch` channel lets a [`Sender`] keep publishing new values;
+//! each `Receiver` always observes the latest one, rather than queueing
+//! every update.
+
+use crate::PingPongCell;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use slab::Slab;
+
+/// Creates a new watch channel, seeded with an initial value.
+pub fn watch<T: Clone + Send>(value: T) -> (Sender<T>, Receiver<T>) {
+    let inner = Arc::new(PingPongCell::new(Some(Watched {
+        value,
+        version: 0,
+        closed: false,
+        next_generation: 0,
+        wakers: Slab::new(),
+    })));
+    let sender = Sender { inner: inner.clone() };
+    let receiver = Receiver { inner, seen: 0, waker_slot: None };
+    (sender, receiver)
+}
+
+/// The Sender has been dropped, so no further values will ever arrive.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Closed();
+
+/// A receiver's registration in `Watched::wakers`: the slab index plus a
+/// generation tag. Slab indices get reused the moment they're freed, so a
+/// bare index isn't enough to tell "my slot" from "someone else's slot
+/// that happens to have the same index now" - the generation makes that
+/// distinguishable.
+type Slot = (usize, u64);
+
+struct Watched<T> {
+    value: T,
+    version: u64,
+    closed: bool,
+    next_generation: u64,
+    wakers: Slab<(u64, Waker)>,
+}
+
+impl<T> Watched<T> {
+    /// Registers (or re-registers) `waker` as the slot for a Receiver,
+    /// reusing `existing` only if it's still the entry that Receiver
+    /// itself installed.
+    fn register(&mut self, existing: Option<Slot>, waker: Waker) -> Slot {
+        if let Some((index, generation)) = existing {
+            if let Some(entry) = self.wakers.get_mut(index) {
+                if entry.0 == generation {
+                    entry.1 = waker;
+                    return (index, generation);
+                }
+            }
+        }
+        let generation = self.next_generation;
+        self.next_generation += 1;
+        let index = self.wakers.insert((generation, waker));
+        (index, generation)
+    }
+
+    /// Removes `slot` from the wakers slab, but only if it hasn't already
+    /// been recycled for a different Receiver's registration.
+    fn release(&mut self, slot: Slot) {
+        let (index, generation) = slot;
+        if self.wakers.get(index).is_some_and(|entry| entry.0 == generation) {
+            self.wakers.remove(index);
+        }
+    }
+}
+
+pub struct Sender<T: Send> {
+    inner: Arc<PingPongCell<Watched<T>>>,
+}
+
+impl<T: Clone + Send> Sender<T> {
+    /// Publishes a new value, waking every parked Receiver.
+    pub fn send(&self, value: T) {
+        let wakers: Vec<Waker> = self.inner.transact(|state| {
+            let watched = state.as_mut().expect("watch state is always present");
+            watched.value = value;
+            watched.version += 1;
+            watched.wakers.drain().map(|(_, waker)| waker).collect()
+        });
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl<T: Send> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let wakers: Vec<Waker> = self.inner.transact(|state| {
+            let watched = state.as_mut().expect("watch state is always present");
+            watched.closed = true;
+            watched.wakers.drain().map(|(_, waker)| waker).collect()
+        });
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+pub struct Receiver<T: Send> {
+    inner: Arc<PingPongCell<Watched<T>>>,
+    seen: u64,
+    // This Receiver's own slot in `Watched::wakers`, if it's currently
+    // parked in `changed()`. `None` once no registration is outstanding
+    // (never parked yet, or the last poll resolved).
+    waker_slot: Option<Slot>,
+}
+
+impl<T: Clone + Send> Receiver<T> {
+    /// Clones out the most recently published value.
+    pub fn borrow(&self) -> T {
+        self.inner.transact(|state| {
+            state.as_ref().expect("watch state is always present").value.clone()
+        })
+    }
+
+    /// Waits until a value newer than the last one observed by this
+    /// Receiver is published, then marks it seen and returns it.
+    pub async fn changed(&mut self) -> Result<T, Closed> {
+        Changed { receiver: self }.await
+    }
+}
+
+impl<T: Clone + Send> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        // The clone hasn't registered a waker of its own yet, regardless
+        // of whether `self` currently has one parked.
+        Receiver { inner: self.inner.clone(), seen: self.seen, waker_slot: None }
+    }
+}
+
+impl<T: Send> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.waker_slot.take() {
+            self.inner.transact(|state| {
+                let watched = state.as_mut().expect("watch state is always present");
+                watched.release(slot);
+            });
+        }
+    }
+}
+
+struct Changed<'a, T: Send> {
+    receiver: &'a mut Receiver<T>,
+}
+
+impl<'a, T: Clone + Send> Future for Changed<'a, T> {
+    type Output = Result<T, Closed>;
+    fn poll(self: Pin<&mut Self>, context: &mut Context) -> Poll<Result<T, Closed>> {
+        let this = Pin::into_inner(self);
+        let seen = this.receiver.seen;
+        let existing_slot = this.receiver.waker_slot;
+        let (outcome, next_slot) = this.receiver.inner.transact(|state| {
+            let watched = state.as_mut().expect("watch state is always present");
+            if watched.version > seen {
+                this.receiver.seen = watched.version;
+                if let Some(slot) = existing_slot {
+                    watched.release(slot);
+                }
+                (Poll::Ready(Ok(watched.value.clone())), None)
+            } else if watched.closed {
+                if let Some(slot) = existing_slot {
+                    watched.release(slot);
+                }
+                (Poll::Ready(Err(Closed())), None)
+            } else {
+                let slot = watched.register(existing_slot, context.waker().clone());
+                (Poll::Pending, Some(slot))
+            }
+        });
+        this.receiver.waker_slot = next_slot;
+        outcome
+    }
+}
@@ -1,188 +1,9 @@
 #![feature(test)]
 
-use futures_micro::{sleep, waker};
 use futures_lite::*;
-use ping_pong_cell::PingPongCell;
-use std::sync::Arc;
-use std::future::Future;
-use std::pin::Pin;
-use std::task::{Context, Poll, Waker};
+use ping_pong_cell::oneshot::{oneshot, Closed};
 use std::thread::spawn;
 
-pub fn oneshot<T: Send>() -> (Sender<T>, Receiver<T>) {
-    let inner = Arc::new(Inner { cell: PingPongCell::new(None) });
-    let sender = Sender { inner: Some(inner.clone()) };
-    let receiver = Receiver { inner: Some(inner) };
-    (sender, receiver)
-}
-
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub struct Closed();
-
-#[derive(Debug)]
-pub enum WaitError {
-    AlreadyAwaited(Waker),
-}
-
-pub struct Sender<T: Send> {
-    inner: Option<Arc<Inner<T>>>,
-}
-
-// Can be polled as a Future to wait for a receiver to be listening.
-impl<T: Send> Sender<T> {
-
-    /// Closes the channel by causing an immediate drop
-    pub fn close(self) { }
-
-    /// Waits for a Receiver to be waiting for us to send something
-    /// (i.e. allows you to produce a value to send on demand).
-    pub async fn wait(self) -> Result<Self, Closed> {
-        while let Some(ref inner) = &self.inner {
-            let wake_me = waker().await;
-            let ret = inner.cell.transact(|state| {
-                match state.take() {
-                    Some(State::Closed) => Poll::Ready(Err(Closed())),
-                    Some(State::Waker(waker)) => {
-                        *state = Some(State::Waker(waker));
-                        Poll::Ready(Ok(()))
-                    }
-                    _ => {
-                        *state = Some(State::Waker(wake_me));
-                        Poll::Pending
-                    }
-                }
-            });
-            match ret {
-                Poll::Pending => sleep().await,
-                Poll::Ready(Ok(())) => { return Ok(self); }
-                Poll::Ready(Err(Closed())) => {return Err(Closed()); }
-            }
-        }
-        Err(Closed())
-    }
-
-    /// Sends a message on the channel
-    pub fn send(self, value: T) -> Result<(), Closed> {
-        if let Some(ref inner) = &self.inner {
-            let ret = inner.cell.transact(|state| {
-                match state.take() {
-                    Some(State::Closed) => Err(Closed()),
-                    Some(State::Waker(waker)) => {
-                        *state = Some(State::Ready(value));
-                        Ok(Some(waker))
-                    }
-                    _ => {
-                        *state = Some(State::Ready(value));
-                        Ok(None)
-                    }
-                }
-            });
-            match ret {
-                Ok(Some(waker)) => {
-                    waker.wake();
-                    Ok(())
-                }
-                Ok(None) => Ok(()),
-                Err(e) => Err(e),
-            }
-        } else {
-            Err(Closed()) // not sure how you got here tbh
-        }
-    }
-}
-
-pub struct Receiver<T: Send> {
-    inner: Option<Arc<Inner<T>>>,
-}
-
-impl<T: Send> Receiver<T> {
-    /// Closes the channel by causing an immediate drop
-    pub fn close(self) { }
-}
-
-enum State<T> {
-    Waker(Waker),
-    Ready(T),
-    Closed,
-}
-
-struct Inner<T> {
-    cell: PingPongCell<State<T>>,
-}
-
-impl<T: Send> Drop for Sender<T> {
-    fn drop(&mut self) {
-        if let Some(inner) = &self.inner {
-            let ret = inner.cell.transact(|state| {
-                match state.take() {
-                    Some(State::Waker(waker)) => {
-                        *state = Some(State::Closed);
-                        Some(waker)
-                    }
-                    // Could be Ready or Closed, either is fine
-                    Some(other) => {
-                        *state = Some(other);
-                        None
-                    }
-                    None => {
-                        *state = Some(State::Closed);
-                        None
-                    }
-                }
-            });
-            if let Some(waker) = ret {
-                waker.wake();
-            }
-        }
-    }
-}
-
-impl<T: Send> Drop for Receiver<T> {
-    fn drop(&mut self) {
-        if let Some(inner) = &self.inner {
-            let ret = inner.cell.transact(|state| {
-                if let Some(State::Waker(waker)) = state.take() {
-                    *state = Some(State::Closed);
-                    Some(waker)
-                } else {
-                    *state = Some(State::Closed);
-                    None
-                }
-            });
-            if let Some(waker) = ret {
-                waker.wake();
-            }
-        }
-    }
-}
-
-impl<T: Send> Future for Receiver<T> {
-    type Output = Result<T, Closed>;
-    fn poll(self: Pin<&mut Self>, context: &mut Context) -> Poll<Result<T, Closed>> {
-        let this = Pin::into_inner(self);
-        if let Some(inner) = &this.inner {
-            inner.cell.transact(|state| {
-                match state.take() {
-                    Some(State::Closed) => Poll::Ready(Err(Closed())),
-                    Some(State::Ready(value)) => Poll::Ready(Ok(value)),
-                    Some(State::Waker(waker)) => {
-                        *state = Some(State::Waker(context.waker().clone()));
-                        waker.wake();
-                        Poll::Pending
-                    }
-                    None => {
-                        *state = Some(State::Waker(context.waker().clone()));
-                        Poll::Pending
-                    }
-                }
-            })
-        } else {
-            Poll::Ready(Err(Closed()))
-        }
-    }
-}
-
-
 #[test]
 fn success_one_thread() {
     let (s,r) = oneshot::<bool>();
@@ -220,6 +41,95 @@ fn close_sender_two_threads() {
     assert_eq!(Err(Closed()), j.join().unwrap());
 }
 
+#[test]
+fn is_canceled_after_receiver_drop() {
+    let (s, r) = oneshot::<bool>();
+    assert!(!s.is_canceled());
+    drop(r);
+    assert!(s.is_canceled());
+}
+
+#[test]
+fn poll_canceled_after_receiver_drop() {
+    let (mut s, r) = oneshot::<bool>();
+    drop(r);
+    future::block_on(future::poll_fn(|cx| s.poll_canceled(cx)));
+}
+
+#[test]
+fn poll_canceled_wakes_on_receiver_drop() {
+    let (mut s, r) = oneshot::<bool>();
+    let t = spawn(move || future::block_on(future::poll_fn(|cx| s.poll_canceled(cx))));
+    drop(r);
+    t.join().unwrap();
+}
+
+#[test]
+fn poll_canceled_does_not_steal_receivers_wakeup() {
+    use std::future::Future;
+    use std::sync::mpsc::channel;
+    use std::sync::Arc;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, Wake};
+
+    struct ChannelWaker(std::sync::mpsc::Sender<()>);
+    impl Wake for ChannelWaker {
+        fn wake(self: Arc<Self>) {
+            let _ = self.0.send(());
+        }
+    }
+
+    let (mut s, mut r) = oneshot::<i32>();
+    let (wake_tx, wake_rx) = channel();
+    let waker = std::task::Waker::from(Arc::new(ChannelWaker(wake_tx)));
+    let mut cx = Context::from_waker(&waker);
+
+    // The Receiver registers its waker in the shared slot, same as a real
+    // executor parking on `r.await` would.
+    assert_eq!(Poll::Pending, Pin::new(&mut r).poll(&mut cx));
+
+    // The Sender checks for cancellation; this must not clobber the
+    // Receiver's already-registered waker.
+    assert_eq!(Poll::Pending, s.poll_canceled(&mut cx));
+
+    s.send(42).unwrap();
+
+    wake_rx
+        .recv_timeout(std::time::Duration::from_secs(2))
+        .expect("receiver's waker was never fired - poll_canceled stole its wakeup");
+    assert_eq!(Poll::Ready(Ok(42)), Pin::new(&mut r).poll(&mut cx));
+}
+
+#[test]
+fn try_recv_empty() {
+    let (s, mut r) = oneshot::<bool>();
+    assert_eq!(Ok(None), r.try_recv());
+    s.send(true).unwrap();
+    assert_eq!(Ok(Some(true)), r.try_recv());
+}
+
+#[test]
+fn try_recv_closed() {
+    let (s, mut r) = oneshot::<bool>();
+    s.close();
+    assert_eq!(Err(Closed()), r.try_recv());
+}
+
+#[test]
+fn blocking_recv_success() {
+    let (s, r) = oneshot::<bool>();
+    let t = spawn(|| r.blocking_recv());
+    s.send(true).unwrap();
+    assert_eq!(Ok(true), t.join().unwrap());
+}
+
+#[test]
+fn blocking_recv_closed() {
+    let (s, r) = oneshot::<bool>();
+    s.close();
+    assert_eq!(Err(Closed()), r.blocking_recv());
+}
+
 #[test]
 fn wait_for_receiver() {
     let (s,r) = oneshot::<bool>();
@@ -231,3 +141,185 @@ fn wait_for_receiver() {
     assert_eq!(Ok(()), j.join().unwrap());
 }
 
+mod watch {
+    use futures_lite::*;
+    use ping_pong_cell::watch::{watch, Closed};
+    use std::thread::spawn;
+
+    #[test]
+    fn borrow_sees_initial_value() {
+        let (_s, r) = watch(1);
+        assert_eq!(1, r.borrow());
+    }
+
+    #[test]
+    fn changed_sees_new_value() {
+        let (s, mut r) = watch(1);
+        s.send(2);
+        assert_eq!(Ok(2), future::block_on(r.changed()));
+        assert_eq!(2, r.borrow());
+    }
+
+    #[test]
+    fn changed_blocks_until_sent() {
+        let (s, mut r) = watch(1);
+        let t = spawn(move || future::block_on(r.changed()));
+        s.send(2);
+        assert_eq!(Ok(2), t.join().unwrap());
+    }
+
+    #[test]
+    fn clones_share_updates_independently() {
+        let (s, mut r1) = watch(1);
+        let mut r2 = r1.clone();
+        s.send(2);
+        assert_eq!(Ok(2), future::block_on(r1.changed()));
+        assert_eq!(Ok(2), future::block_on(r2.changed()));
+    }
+
+    #[test]
+    fn closed_after_sender_drop() {
+        let (s, mut r) = watch(1);
+        drop(s);
+        assert_eq!(Err(Closed()), future::block_on(r.changed()));
+    }
+
+    #[test]
+    fn repeated_polls_do_not_accumulate_wakers() {
+        use std::future::Future;
+        use std::pin::pin;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::task::{Context, Poll, Wake};
+
+        struct CountingWaker(AtomicUsize);
+        impl Wake for CountingWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let (s, mut r) = watch(1);
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = std::task::Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        // Poll `changed()` several times with no new value in between, as
+        // a `select!` loop would if some unrelated branch kept waking the
+        // task. Each poll should replace this Receiver's one waker slot,
+        // not add another.
+        for _ in 0..5 {
+            let fut = pin!(r.changed());
+            assert_eq!(Poll::Pending, fut.poll(&mut cx));
+        }
+
+        s.send(2);
+        assert_eq!(1, counter.0.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn dropped_receiver_does_not_steal_a_reused_slot() {
+        use std::future::Future;
+        use std::pin::pin;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::task::{Context, Poll, Wake};
+
+        struct CountingWaker(AtomicUsize);
+        impl Wake for CountingWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let (s, mut a) = watch(1);
+
+        // Park `a`, claiming a fresh slot.
+        let a_counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let a_waker = std::task::Waker::from(a_counter.clone());
+        let mut a_cx = Context::from_waker(&a_waker);
+        {
+            let fut = pin!(a.changed());
+            assert_eq!(Poll::Pending, fut.poll(&mut a_cx));
+        }
+
+        // Publish a value. This drains (and frees) every slot without `a`
+        // being repolled, so `a`'s remembered slot is now stale.
+        s.send(2);
+
+        // `b` catches up to the new value, then parks again, reusing the
+        // index `a` used to occupy.
+        let mut b = a.clone();
+        let b_counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let b_waker = std::task::Waker::from(b_counter.clone());
+        let mut b_cx = Context::from_waker(&b_waker);
+        {
+            let fut = pin!(b.changed());
+            assert_eq!(Poll::Ready(Ok(2)), fut.poll(&mut b_cx));
+        }
+        {
+            let fut = pin!(b.changed());
+            assert_eq!(Poll::Pending, fut.poll(&mut b_cx));
+        }
+
+        // Dropping the stale `a` must not tear out `b`'s live registration.
+        drop(a);
+
+        s.send(3);
+        assert_eq!(1, b_counter.0.load(Ordering::SeqCst));
+    }
+}
+
+mod pool {
+    use futures_lite::*;
+    use ping_pong_cell::oneshot::pool::Pool;
+    use ping_pong_cell::oneshot::Closed;
+    use std::thread::spawn;
+
+    #[test]
+    fn success_one_thread() {
+        let pool = Pool::<bool>::new();
+        let (s, r) = pool.channel();
+        assert_eq!((), s.send(true).unwrap());
+        assert_eq!(Ok(true), future::block_on(r));
+    }
+
+    #[test]
+    fn close_sender_one_thread() {
+        let pool = Pool::<bool>::new();
+        let (s, r) = pool.channel();
+        s.close();
+        assert_eq!(Err(Closed()), future::block_on(r));
+    }
+
+    #[test]
+    fn close_receiver_one_thread() {
+        let pool = Pool::<bool>::new();
+        let (s, r) = pool.channel();
+        r.close();
+        assert_eq!(Err(Closed()), s.send(true));
+    }
+
+    #[test]
+    fn success_two_threads() {
+        let pool = Pool::<bool>::new();
+        let (s, r) = pool.channel();
+        let t = spawn(|| future::block_on(r));
+        assert_eq!((), s.send(true).unwrap());
+        assert_eq!(Ok(true), t.join().unwrap());
+    }
+
+    #[test]
+    fn slots_are_recycled() {
+        let pool = Pool::<bool>::new();
+        let (s, r) = pool.channel();
+        s.send(true).unwrap();
+        assert_eq!(Ok(true), future::block_on(r));
+        // Both halves of the first channel are gone, so its slot is free and
+        // the next channel reuses it; that channel must start out empty
+        // rather than seeing the first one's leftover state.
+        let (s, r) = pool.channel();
+        s.send(false).unwrap();
+        assert_eq!(Ok(false), future::block_on(r));
+    }
+}